@@ -0,0 +1,254 @@
+//! Closed-form (non-sampling) computation of the extra-inning-length distribution.
+//!
+//! `simulate_game`'s run-scoring loop is a geometric trial: a single trial succeeds with
+//! probability `q = (percent - 1) / 100` (since `gen_range(1..=100) < percent` holds for
+//! `percent - 1` of the 100 possible outcomes), so runs scored in a half-inning `R` satisfy
+//! `P(R = k) = q^k * (1 - q)`. This module computes the resulting distribution of total innings
+//! played analytically instead of by sampling millions of games.
+
+#[cfg(test)]
+mod test;
+
+use crate::NUM_EXTRA_INNING_LENGTHS;
+
+/// Precision used for the analytical computation in [`expected_inning_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExactPrecision {
+    /// Compute everything in `f64`; fast, and precise enough for any realistic `num_games`.
+    F64,
+    /// Carry the extra-innings geometric series through exact rationals before converting to
+    /// `f64` for the final expected counts, falling back to `f64` partway through the tail if
+    /// `i128` can no longer represent the exact fraction. Slower, and mainly useful for
+    /// auditing how much the `F64` path has drifted from the closed-form answer.
+    Rational,
+}
+
+impl std::str::FromStr for ExactPrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "f64" => Ok(Self::F64),
+            "rational" => Ok(Self::Rational),
+            other => Err(format!(
+                "unknown exact precision `{}` (expected `f64` or `rational`)",
+                other
+            )),
+        }
+    }
+}
+
+/// A reduced fraction `numerator / denominator`, used to carry the extra-innings geometric
+/// series through exact arithmetic before it's converted to `f64`.
+#[derive(Debug, Clone, Copy)]
+struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    fn new(numerator: i128, denominator: i128) -> Self {
+        let divisor = gcd(numerator.abs(), denominator.abs()).max(1);
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    fn sub_from_one(self) -> Self {
+        Self::new(self.denominator - self.numerator, self.denominator)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        let numerator = self.numerator.checked_mul(other.numerator)?;
+        let denominator = self.denominator.checked_mul(other.denominator)?;
+        Some(Self::new(numerator, denominator))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Truncated distribution of `home_runs - away_runs` for a single inning in which both teams
+/// draw iid `Geometric(q)` runs, each truncated at `max_runs`.
+///
+/// Returns a vector indexed by `diff + max_runs`, i.e. index `max_runs` holds `P(diff == 0)`.
+fn diff_distribution(q: f64, max_runs: u32) -> Vec<f64> {
+    let max_runs = max_runs as usize;
+
+    let mut single = vec![0.0; max_runs + 1];
+    let mut mass = 1.0 - q;
+    for slot in &mut single {
+        *slot = mass;
+        mass *= q;
+    }
+
+    let mut diff = vec![0.0; 2 * max_runs + 1];
+    for (home, &home_p) in single.iter().enumerate() {
+        for (away, &away_p) in single.iter().enumerate() {
+            let offset = home as i64 - away as i64 + max_runs as i64;
+            diff[offset as usize] += home_p * away_p;
+        }
+    }
+
+    diff
+}
+
+/// Convolves two probability mass functions represented as dense vectors.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, &ai) in a.iter().enumerate() {
+        if ai == 0.0 {
+            continue;
+        }
+
+        for (j, &bj) in b.iter().enumerate() {
+            out[i + j] += ai * bj;
+        }
+    }
+
+    out
+}
+
+/// How much per-inning `Geometric(q)` mass [`effective_max_runs`] tolerates truncating away.
+/// Sampling noise at any realistic `--num-games` swamps `1e-6`, so this keeps `--exact` exact
+/// without `max_runs` blowing up for high but legal scoring percents.
+const MAX_RUNS_TRUNCATION_EPSILON: f64 = 1e-6;
+
+/// The smallest `max_runs` for which truncating a `Geometric(q)` distribution at `max_runs`
+/// drops less than [`MAX_RUNS_TRUNCATION_EPSILON`] of its mass, i.e. the smallest `n` with
+/// `q^(n + 1) < MAX_RUNS_TRUNCATION_EPSILON`.
+fn min_max_runs_for_epsilon(q: f64) -> u32 {
+    if q <= 0.0 {
+        return 0;
+    }
+
+    let n_plus_one = (MAX_RUNS_TRUNCATION_EPSILON.ln() / q.ln()).ceil();
+    (n_plus_one as u32).saturating_sub(1)
+}
+
+/// `max_runs`, bumped up (never down) so that truncating the `Geometric(q)` per-inning run
+/// distribution there drops a negligible fraction of its mass. Left as-is, `--exact-max-runs`'s
+/// default of 60 silently drops ~29% of the per-inning distribution at `--regular-score-percent
+/// 99` (`q = 0.98`, so `q^61` is far from negligible), which would make `--exact` materially
+/// wrong for legal high-scoring inputs instead of exact.
+fn effective_max_runs(score_percent: u8, max_runs: u32) -> u32 {
+    let q = f64::from(score_percent - 1) / 100.0;
+    max_runs.max(min_max_runs_for_epsilon(q))
+}
+
+/// `P(home_team_runs == away_team_runs)` after `regulation_innings` innings of regulation play.
+fn tied_after_regulation_probability(regular_score_percent: u8, regulation_innings: u8, max_runs: u32) -> f64 {
+    let q = f64::from(regular_score_percent - 1) / 100.0;
+    let max_runs = effective_max_runs(regular_score_percent, max_runs);
+    let per_inning = diff_distribution(q, max_runs);
+
+    let mut total = per_inning.clone();
+    for _ in 1..regulation_innings {
+        total = convolve(&total, &per_inning);
+    }
+
+    let zero_index = max_runs as usize * regulation_innings as usize;
+    total[zero_index]
+}
+
+/// `P(L = m)` for `m = 1, 2, ...` up to `NUM_EXTRA_INNING_LENGTHS`, where `L` is the number of
+/// extra innings played given the game reached extras (both teams draw iid runs each extra
+/// inning, and the game continues iff they tie).
+fn extra_innings_lengths(extra_innings_score_percent: u8, precision: ExactPrecision) -> Vec<f64> {
+    match precision {
+        ExactPrecision::F64 => {
+            let q = f64::from(extra_innings_score_percent - 1) / 100.0;
+            let continuation_probability = (1.0 - q) / (1.0 + q);
+
+            let mut lengths = Vec::with_capacity(NUM_EXTRA_INNING_LENGTHS);
+            let mut mass = 1.0 - continuation_probability;
+            for _ in 0..NUM_EXTRA_INNING_LENGTHS {
+                if mass < f64::EPSILON {
+                    break;
+                }
+
+                lengths.push(mass);
+                mass *= continuation_probability;
+            }
+
+            lengths
+        }
+        ExactPrecision::Rational => {
+            // q = (percent - 1) / 100, so 1 - q = (101 - percent) / 100 and 1 + q = (99 + percent) / 100.
+            let percent = i128::from(extra_innings_score_percent);
+            let continuation_probability = Rational::new(101 - percent, 99 + percent);
+            let continuation_f64 = continuation_probability.to_f64();
+
+            let mut exact_mass = Some(continuation_probability.sub_from_one());
+            let mut mass_f64 = exact_mass.expect("just constructed").to_f64();
+
+            let mut lengths = Vec::with_capacity(NUM_EXTRA_INNING_LENGTHS);
+            for _ in 0..NUM_EXTRA_INNING_LENGTHS {
+                if mass_f64 < f64::EPSILON {
+                    break;
+                }
+
+                lengths.push(mass_f64);
+
+                match exact_mass.and_then(|mass| mass.checked_mul(continuation_probability)) {
+                    Some(next) => {
+                        exact_mass = Some(next);
+                        mass_f64 = next.to_f64();
+                    }
+                    // i128 can no longer represent the exact fraction; by this point in the
+                    // tail the probability is already astronomically small, so finish it out
+                    // in plain f64 rather than cutting the distribution short.
+                    None => {
+                        exact_mass = None;
+                        mass_f64 *= continuation_f64;
+                    }
+                }
+            }
+
+            lengths
+        }
+    }
+}
+
+/// Computes, via closed form rather than sampling, the expected number of games of each
+/// extra-inning length for `num_games` games played under the given scoring rates.
+///
+/// `skip_regulation_innings` mirrors `--skip-regulation-innings`: when set, the game is assumed
+/// to already be tied entering extras, so `tied_after_regulation_probability` is `1.0` instead
+/// of being computed by convolving `regulation_innings` innings of regulation play.
+///
+/// Returns `(tied_after_regulation_probability, expected_counts)`, where `expected_counts[i]`
+/// is the expected number of games with `i + regulation_innings + 1` total innings.
+pub fn expected_inning_counts(
+    regular_score_percent: u8,
+    extra_innings_score_percent: u8,
+    regulation_innings: u8,
+    max_runs: u32,
+    precision: ExactPrecision,
+    num_games: usize,
+    skip_regulation_innings: bool,
+) -> (f64, Vec<f64>) {
+    let tied_probability = if skip_regulation_innings {
+        1.0
+    } else {
+        tied_after_regulation_probability(regular_score_percent, regulation_innings, max_runs)
+    };
+    let length_probabilities = extra_innings_lengths(extra_innings_score_percent, precision);
+
+    let expected_counts = length_probabilities
+        .into_iter()
+        .map(|length_probability| num_games as f64 * tied_probability * length_probability)
+        .collect();
+
+    (tied_probability, expected_counts)
+}
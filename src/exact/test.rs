@@ -0,0 +1,162 @@
+use super::*;
+use crate::output::OutputFormat;
+use crate::{reset_counts, simulate_inning_counts, Options, INNING_COUNTS};
+use std::sync::atomic::Ordering::SeqCst;
+
+#[test]
+fn diff_distribution_sums_to_one() {
+    let dist = diff_distribution(0.39, 60);
+
+    let total: f64 = dist.iter().sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn diff_distribution_is_symmetric_around_zero() {
+    let dist = diff_distribution(0.39, 10);
+
+    for offset in 0..=10 {
+        assert!((dist[10 + offset] - dist[10 - offset]).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn tied_after_regulation_probability_matches_no_scoring_case() {
+    // regular_score_percent of 1 means q = 0, so every inning is scoreless and the game is
+    // tied 0-0 after any number of regulation innings.
+    let probability = tied_after_regulation_probability(1, 9, 60);
+    assert!((probability - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn effective_max_runs_bumps_a_too_low_max_runs_for_high_score_percent() {
+    // At `--regular-score-percent 99` (`q = 0.98`), truncating at the default `max_runs` of 60
+    // drops a large, non-negligible fraction of the per-inning distribution (`q^61` isn't close
+    // to zero), so `effective_max_runs` must raise it.
+    assert!(effective_max_runs(99, 60) > 60);
+}
+
+#[test]
+fn effective_max_runs_leaves_an_already_sufficient_max_runs_alone() {
+    assert_eq!(effective_max_runs(40, 60), 60);
+}
+
+#[test]
+fn tied_after_regulation_probability_converges_once_auto_bumped_at_high_score_percent() {
+    // Before `effective_max_runs`, passing the default `max_runs` of 60 at
+    // `--regular-score-percent 99` dropped ~29% of each inning's mass and diverged badly from
+    // the true answer. Now that it's auto-bumped internally, passing a requested `max_runs` far
+    // larger than necessary should agree closely with passing the (too-low) default.
+    let low_requested = tied_after_regulation_probability(99, 9, 60);
+    let high_requested = tied_after_regulation_probability(99, 9, 800);
+
+    assert!(
+        (low_requested - high_requested).abs() < 1e-5,
+        "low-requested-max_runs probability {} vs high-requested-max_runs probability {}",
+        low_requested,
+        high_requested
+    );
+}
+
+#[test]
+fn extra_innings_lengths_sum_to_one() {
+    let f64_lengths = extra_innings_lengths(40, ExactPrecision::F64);
+    let rational_lengths = extra_innings_lengths(40, ExactPrecision::Rational);
+
+    let f64_total: f64 = f64_lengths.iter().sum();
+    let rational_total: f64 = rational_lengths.iter().sum();
+
+    assert!((f64_total - 1.0).abs() < 1e-6);
+    assert!((rational_total - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn extra_innings_lengths_f64_and_rational_agree() {
+    let f64_lengths = extra_innings_lengths(40, ExactPrecision::F64);
+    let rational_lengths = extra_innings_lengths(40, ExactPrecision::Rational);
+
+    assert_eq!(f64_lengths.len(), rational_lengths.len());
+    for (f64_value, rational_value) in f64_lengths.iter().zip(rational_lengths.iter()) {
+        assert!((f64_value - rational_value).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn expected_inning_counts_treats_skip_regulation_innings_as_already_tied() {
+    let (tied_probability, _) = expected_inning_counts(40, 40, 9, 60, ExactPrecision::F64, 1000, true);
+    assert_eq!(tied_probability, 1.0);
+}
+
+#[test]
+fn exact_precision_from_str() {
+    assert_eq!("f64".parse(), Ok(ExactPrecision::F64));
+    assert_eq!("rational".parse(), Ok(ExactPrecision::Rational));
+    assert!("bogus".parse::<ExactPrecision>().is_err());
+}
+
+// Regression test for a boundary bug where `simulate_game`'s run-checker decided the first
+// extra half-inning's rate from the pre-step inning number, so it was simulated with
+// `regular_score_percent` instead of `extra_innings_score_percent` whenever the two differ.
+// `--exact` models the rates cleanly split at regulation, so this cross-checks the two against
+// each other with asymmetric rates, which the rest of this suite's symmetric/degenerate cases
+// can't catch.
+#[test]
+fn exact_matches_large_seeded_simulation_with_asymmetric_rates() {
+    let regular_score_percent = 40;
+    let extra_innings_score_percent = 80;
+    let regulation_innings = 9;
+    let num_games = 200_000;
+
+    let opts = Options {
+        num_games,
+        regular_score_percent,
+        extra_innings_score_percent,
+        home_score_percent: None,
+        away_score_percent: None,
+        regulation_innings,
+        skip_regulation_innings: false,
+        disable_parallel: false,
+        exact: false,
+        exact_precision: ExactPrecision::F64,
+        exact_max_runs: 60,
+        fit: false,
+        target_histogram: None,
+        time_limit_ms: 950,
+        fit_num_games: 20_000,
+        seed: Some(12345),
+        output: OutputFormat::Table,
+        output_file: None,
+    };
+
+    reset_counts();
+    simulate_inning_counts(
+        &opts,
+        num_games,
+        regular_score_percent,
+        regular_score_percent,
+        extra_innings_score_percent,
+    );
+
+    let (_, expected_counts) = expected_inning_counts(
+        regular_score_percent,
+        extra_innings_score_percent,
+        regulation_innings,
+        60,
+        ExactPrecision::F64,
+        num_games,
+        false,
+    );
+
+    for (offset, count) in INNING_COUNTS.iter().enumerate().take(5) {
+        let simulated_fraction = count.load(SeqCst) as f64 / num_games as f64;
+        let exact_fraction = expected_counts[offset] / num_games as f64;
+
+        assert!(
+            (simulated_fraction - exact_fraction).abs() < 0.01,
+            "offset {}: simulated fraction {} vs exact fraction {}",
+            offset,
+            simulated_fraction,
+            exact_fraction
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Simulated-annealing search for the scoring rates that best reproduce an observed
+//! extra-inning-length distribution.
+//!
+//! Each candidate `(regular_score_percent, extra_innings_score_percent)` pair is scored by
+//! simulating `--fit-num-games` games (far fewer than a full `--num-games` run, so a search
+//! iteration stays cheap) and measuring the squared error between the resulting
+//! extra-inning-length fractions and the target's, using the existing
+//! [`crate::simulate_inning_counts`] machinery. The search proposes a neighbor by nudging one
+//! rate by +-1 (clamped to 1..=99), accepts it outright if it lowers the error, and otherwise
+//! accepts it with probability `exp(-delta / temperature)`, cooling `temperature` geometrically
+//! toward zero as `--time-limit-ms` elapses.
+
+#[cfg(test)]
+mod test;
+
+use crate::{Options, NUM_EXTRA_INNING_LENGTHS};
+use rand::{thread_rng, Rng};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// A candidate pair of scoring rates, the two free parameters the search is fitting.
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate {
+    pub regular_score_percent: u8,
+    pub extra_innings_score_percent: u8,
+}
+
+/// The best candidate [`fit`] found and how closely it reproduced the target histogram.
+#[derive(Debug)]
+pub struct FitResult {
+    pub candidate: Candidate,
+    pub residual_error: f64,
+}
+
+/// Reads a target histogram as `innings,fraction` rows. A header row (or any other line whose
+/// first field doesn't parse as an inning count) is tolerated and skipped.
+pub fn parse_target_histogram(path: &Path) -> io::Result<Vec<(u8, f64)>> {
+    let lines = BufReader::new(File::open(path)?)
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(lines.iter().filter_map(|line| parse_target_row(line)).collect())
+}
+
+fn parse_target_row(line: &str) -> Option<(u8, f64)> {
+    let mut fields = line.split(',');
+    let innings = fields.next()?.trim().parse::<u8>().ok()?;
+    let fraction = fields.next()?.trim().parse::<f64>().ok()?;
+
+    Some((innings, fraction))
+}
+
+/// Expands `target` into the same dense, offset-indexed layout as [`INNING_COUNTS`][crate], so
+/// it can be compared directly against a simulated fraction vector.
+fn target_fractions(target: &[(u8, f64)], regulation_innings: u8) -> Vec<f64> {
+    let mut fractions = vec![0.0; NUM_EXTRA_INNING_LENGTHS];
+
+    for &(innings, fraction) in target {
+        if innings <= regulation_innings {
+            continue;
+        }
+
+        let offset = innings as usize - (regulation_innings as usize + 1);
+        if let Some(slot) = fractions.get_mut(offset) {
+            *slot = fraction;
+        }
+    }
+
+    fractions
+}
+
+fn nudge(percent: u8, rng: &mut impl Rng) -> u8 {
+    let delta: i16 = if rng.gen_bool(0.5) { 1 } else { -1 };
+    (i16::from(percent) + delta).clamp(1, 99) as u8
+}
+
+fn propose_neighbor(candidate: Candidate, rng: &mut impl Rng) -> Candidate {
+    if rng.gen_bool(0.5) {
+        Candidate {
+            regular_score_percent: nudge(candidate.regular_score_percent, rng),
+            ..candidate
+        }
+    } else {
+        Candidate {
+            extra_innings_score_percent: nudge(candidate.extra_innings_score_percent, rng),
+            ..candidate
+        }
+    }
+}
+
+fn score(opts: &Options, candidate: Candidate, target_fractions: &[f64]) -> f64 {
+    let simulated = crate::simulated_fractions(
+        opts,
+        opts.fit_num_games,
+        candidate.regular_score_percent,
+        candidate.regular_score_percent,
+        candidate.extra_innings_score_percent,
+    );
+
+    simulated
+        .iter()
+        .zip(target_fractions)
+        .map(|(simulated, target)| (simulated - target).powi(2))
+        .sum()
+}
+
+/// Searches for the candidate pair whose simulated extra-inning-length distribution best
+/// matches `target`, starting from `opts`'s `--regular-score-percent`/
+/// `--extra-innings-score-percent` and spending up to `time_limit` of wall-clock time.
+pub fn fit(opts: &Options, target: &[(u8, f64)], time_limit: Duration) -> FitResult {
+    let target_fractions = target_fractions(target, opts.regulation_innings);
+    let mut rng = thread_rng();
+
+    let mut current = Candidate {
+        regular_score_percent: opts.regular_score_percent,
+        extra_innings_score_percent: opts.extra_innings_score_percent,
+    };
+
+    const START_TEMPERATURE: f64 = 1.0;
+    const END_TEMPERATURE: f64 = 1e-3;
+    // Started before the initial `score()` call so that candidate's simulation cost counts
+    // against `time_limit` too; otherwise a slow first score could eat a large chunk of the
+    // budget for free and leave the search almost no iterations to anneal with.
+    let start = Instant::now();
+    let mut current_error = score(opts, current, &target_fractions);
+
+    let mut best = current;
+    let mut best_error = current_error;
+
+    while start.elapsed() < time_limit {
+        let progress = start.elapsed().as_secs_f64() / time_limit.as_secs_f64();
+        // Geometric decay from `START_TEMPERATURE` to `END_TEMPERATURE`: `temperature(0) ==
+        // START_TEMPERATURE`, `temperature(1) == END_TEMPERATURE`, ratio between consecutive
+        // steps constant throughout rather than the fixed per-step amount a linear schedule
+        // would subtract.
+        let temperature = (START_TEMPERATURE * (END_TEMPERATURE / START_TEMPERATURE).powf(progress)).max(f64::EPSILON);
+
+        let neighbor = propose_neighbor(current, &mut rng);
+        let neighbor_error = score(opts, neighbor, &target_fractions);
+        let delta = neighbor_error - current_error;
+
+        if delta <= 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+            current = neighbor;
+            current_error = neighbor_error;
+
+            if current_error < best_error {
+                best = current;
+                best_error = current_error;
+            }
+        }
+    }
+
+    FitResult {
+        candidate: best,
+        residual_error: best_error,
+    }
+}
@@ -0,0 +1,43 @@
+use super::*;
+use std::fs;
+
+#[test]
+fn target_fractions_expands_into_dense_offset_indexed_vector() {
+    let target = vec![(10, 0.6), (12, 0.1)];
+    let fractions = target_fractions(&target, 9);
+
+    assert_eq!(fractions[0], 0.6);
+    assert_eq!(fractions[1], 0.0);
+    assert_eq!(fractions[2], 0.1);
+}
+
+#[test]
+fn target_fractions_ignores_innings_at_or_below_regulation() {
+    let target = vec![(9, 0.5), (8, 0.5)];
+    let fractions = target_fractions(&target, 9);
+
+    assert!(fractions.iter().all(|&fraction| fraction == 0.0));
+}
+
+#[test]
+fn nudge_stays_within_valid_range() {
+    let mut rng = rand::thread_rng();
+
+    for percent in [1, 2, 50, 98, 99] {
+        for _ in 0..20 {
+            let nudged = nudge(percent, &mut rng);
+            assert!((1..=99).contains(&nudged));
+        }
+    }
+}
+
+#[test]
+fn parse_target_histogram_skips_header_and_parses_rows() {
+    let path = std::env::temp_dir().join("varball_fit_test_target_histogram.csv");
+    fs::write(&path, "innings,fraction\n10,0.6\n11,0.25\n12,0.15\n").unwrap();
+
+    let target = parse_target_histogram(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(target, vec![(10, 0.6), (11, 0.25), (12, 0.15)]);
+}
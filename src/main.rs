@@ -1,21 +1,34 @@
+mod exact;
+mod fit;
+mod output;
 #[cfg(test)]
 mod test;
 
 use array_macro::array;
-use num_format::{Buffer, Locale};
-use rand::{thread_rng, Rng};
+use exact::ExactPrecision;
+use output::{InningCount, InningHistogram, OutputFormat, WinBreakdown};
+use rand::{rngs::SmallRng, thread_rng, Rng, SeedableRng};
 use rayon::prelude::*;
 use std::{
     cmp::Ordering,
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
     sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    time::Duration,
 };
 use structopt::StructOpt;
 
 const NUM_INNINGS_DEFAULT: u8 = 9;
 
-const NUM_EXTRA_INNING_LENGTHS: usize = (u8::MAX - 10) as usize;
+// Sized to cover the worst case of `--regulation-innings 1`, where a game can run all the way
+// to `u8::MAX` innings before the array index (`num_innings - (regulation_innings + 1)`) would
+// overflow.
+const NUM_EXTRA_INNING_LENGTHS: usize = u8::MAX as usize;
 static INNING_COUNTS: [AtomicUsize; NUM_EXTRA_INNING_LENGTHS] =
     array![_ => AtomicUsize::new(0); NUM_EXTRA_INNING_LENGTHS];
+static HOME_WINS: AtomicUsize = AtomicUsize::new(0);
+static AWAY_WINS: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(StructOpt)]
 struct Options {
@@ -31,28 +44,100 @@ struct Options {
     #[structopt(short, long, default_value = "40")]
     extra_innings_score_percent: u8,
 
-    /// Whether to skip the first nine innings and assume all games will go into extra innings.
-    #[structopt(short, long)]
-    skip_first_nine_innings: bool,
+    /// The geometric factor for a chance for the home team to score in a non-extra inning.
+    /// Defaults to `--regular-score-percent`. Ignored in `--exact` mode, which only models the
+    /// symmetric case.
+    #[structopt(long)]
+    home_score_percent: Option<u8>,
+
+    /// The geometric factor for a chance for the away team to score in a non-extra inning.
+    /// Defaults to `--regular-score-percent`. Ignored in `--exact` mode, which only models the
+    /// symmetric case.
+    #[structopt(long)]
+    away_score_percent: Option<u8>,
+
+    /// How many innings constitute a regulation game, after which the game may go to extras.
+    #[structopt(long, default_value = "9")]
+    regulation_innings: u8,
+
+    /// Whether to skip the regulation innings and assume all games will go into extra innings.
+    /// In `--exact` mode this treats the tied-after-regulation probability as 1.0 instead of
+    /// convolving `--regulation-innings` innings of regular-rate scoring.
+    #[structopt(long)]
+    skip_regulation_innings: bool,
 
     /// Disable simulating games in parallel.
     #[structopt(short, long)]
     disable_parallel: bool,
+
+    /// Compute the extra-inning-length distribution analytically instead of by sampling games.
+    #[structopt(long)]
+    exact: bool,
+
+    /// Precision to use for the analytical computation when `--exact` is passed (`f64` or
+    /// `rational`).
+    #[structopt(long, default_value = "f64")]
+    exact_precision: ExactPrecision,
+
+    /// The maximum per-team runs considered per inning when convolving the regulation-innings
+    /// score distribution in `--exact` mode. Automatically raised (never lowered) if it's too
+    /// low to keep the truncated per-inning distribution negligibly close to exact for the
+    /// chosen `--regular-score-percent` -- e.g. the default of 60 is nowhere near enough at
+    /// `--regular-score-percent 99`.
+    #[structopt(long, default_value = "60")]
+    exact_max_runs: u32,
+
+    /// Switch to fitting mode: instead of simulating or analytically computing a histogram,
+    /// search for the `--regular-score-percent`/`--extra-innings-score-percent` pair that best
+    /// reproduces `--target-histogram` via simulated annealing. Requires `--target-histogram`.
+    #[structopt(long)]
+    fit: bool,
+
+    /// CSV of `innings,fraction` rows (a header row is tolerated and skipped) giving the
+    /// observed extra-inning-length distribution to fit against. Required when `--fit` is
+    /// passed.
+    #[structopt(long, parse(from_os_str))]
+    target_histogram: Option<PathBuf>,
+
+    /// Wall-clock budget for the `--fit` search, after which the best candidate pair found so
+    /// far is reported.
+    #[structopt(long, default_value = "950")]
+    time_limit_ms: u64,
+
+    /// How many games to simulate per candidate pair while `--fit` is searching. Kept far below
+    /// `--num-games` so the search can run enough iterations within `--time-limit-ms` to anneal.
+    #[structopt(long, default_value = "20000")]
+    fit_num_games: usize,
+
+    /// Seed for the RNG used to simulate games. When omitted, a random seed is chosen, so
+    /// results aren't reproducible from one run to the next.
+    #[structopt(long)]
+    seed: Option<u64>,
+
+    /// Output format for the inning histogram (`table`, `csv`, or `json`).
+    #[structopt(long, default_value = "table")]
+    output: OutputFormat,
+
+    /// Optional file to write the output to, instead of stdout.
+    #[structopt(long, parse(from_os_str))]
+    output_file: Option<PathBuf>,
 }
 
-type RunChecker = Box<dyn Fn(&GameState) -> u8>;
+type RunChecker = Box<dyn Fn(&GameState, &mut SmallRng) -> u8>;
 
 struct Game {
     state: GameState,
+    rng: SmallRng,
     run_checker: RunChecker,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct GameState {
     home_team_runs: u8,
     away_team_runs: u8,
     inning: u8,
     half_inning: HalfInning,
+    regulation_innings: u8,
 }
 
 #[derive(Debug)]
@@ -62,13 +147,12 @@ struct FinalScore {
     inning: u8,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum HalfInning {
     Top,
     Bottom,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, PartialEq, Eq)]
 enum Team {
     Home,
@@ -81,6 +165,18 @@ impl Default for HalfInning {
     }
 }
 
+impl Default for GameState {
+    fn default() -> Self {
+        Self {
+            home_team_runs: 0,
+            away_team_runs: 0,
+            inning: 0,
+            half_inning: HalfInning::default(),
+            regulation_innings: NUM_INNINGS_DEFAULT,
+        }
+    }
+}
+
 impl HalfInning {
     fn flip(&mut self) {
         *self = match self {
@@ -91,9 +187,13 @@ impl HalfInning {
 }
 
 impl Game {
-    fn new(run_checker: RunChecker) -> Self {
+    fn new(seed: u64, regulation_innings: u8, run_checker: RunChecker) -> Self {
         Self {
-            state: Default::default(),
+            state: GameState {
+                regulation_innings,
+                ..Default::default()
+            },
+            rng: SmallRng::seed_from_u64(seed),
             run_checker,
         }
     }
@@ -101,10 +201,10 @@ impl Game {
 
 impl GameState {
     fn is_over(&self) -> bool {
-        (self.inning == NUM_INNINGS_DEFAULT &&
+        (self.inning == self.regulation_innings &&
             self.half_inning == HalfInning::Top &&
             self.home_team_runs > self.away_team_runs) ||
-            (self.inning >= NUM_INNINGS_DEFAULT &&
+            (self.inning >= self.regulation_innings &&
                 self.half_inning == HalfInning::Bottom &&
                 self.home_team_runs != self.away_team_runs)
     }
@@ -132,7 +232,8 @@ impl GameState {
 impl Game {
     fn complete(&mut self) -> FinalScore {
         while !self.state.is_over() {
-            self.state.step(self.run_checker.as_ref()(&self.state));
+            let runs_scored = self.run_checker.as_ref()(&self.state, &mut self.rng);
+            self.state.step(runs_scored);
         }
 
         FinalScore {
@@ -143,7 +244,6 @@ impl Game {
     }
 }
 
-#[allow(dead_code)]
 impl FinalScore {
     fn winner(&self) -> Team {
         match self.home_team.cmp(&self.away_team) {
@@ -155,97 +255,219 @@ impl FinalScore {
 }
 
 fn simulate_game(
-    regular_score_percent: u8,
+    home_score_percent: u8,
+    away_score_percent: u8,
     extra_innings_score_percent: u8,
-    skip_first_nine_innings: bool,
+    regulation_innings: u8,
+    skip_regulation_innings: bool,
+    seed: u64,
 ) -> FinalScore {
-    let mut game = Game::new(Box::new(move |state| {
-        std::iter::repeat(1)
-            .take_while(|_| {
-                if state.inning > NUM_INNINGS_DEFAULT {
-                    thread_rng().gen_range(1..=100) < extra_innings_score_percent
-                } else {
-                    thread_rng().gen_range(1..=100) < regular_score_percent
-                }
-            })
-            .sum()
-    }));
+    let mut game = Game::new(
+        seed,
+        regulation_innings,
+        Box::new(move |state, rng| {
+            std::iter::repeat(1)
+                .take_while(|_| {
+                    let mut upcoming_half = state.half_inning;
+                    upcoming_half.flip();
+
+                    // `state.inning` only gets incremented by `step` once the half flips to
+                    // `Top` (see `GameState::step`), so the inning the *upcoming* half-inning
+                    // belongs to is one past `state.inning` when we're about to flip into a new
+                    // `Top`, and `state.inning` itself otherwise.
+                    let upcoming_inning = match upcoming_half {
+                        HalfInning::Top => state.inning + 1,
+                        HalfInning::Bottom => state.inning,
+                    };
+
+                    if upcoming_inning > regulation_innings {
+                        rng.gen_range(1..=100) < extra_innings_score_percent
+                    } else {
+                        let score_percent = match upcoming_half {
+                            HalfInning::Top => away_score_percent,
+                            HalfInning::Bottom => home_score_percent,
+                        };
+
+                        rng.gen_range(1..=100) < score_percent
+                    }
+                })
+                .sum()
+        }),
+    );
 
-    if skip_first_nine_innings {
-        game.state.inning = NUM_INNINGS_DEFAULT;
+    if skip_regulation_innings {
+        game.state.inning = regulation_innings;
     }
 
     game.complete()
 }
 
-fn update_inning_count(num_innings: u8) {
-    let inning_index = num_innings as usize - 10;
+fn update_inning_count(num_innings: u8, regulation_innings: u8) {
+    let inning_index = num_innings as usize - (regulation_innings as usize + 1);
     INNING_COUNTS[inning_index].fetch_add(1, SeqCst);
 }
 
+fn update_win_count(final_score: &FinalScore) {
+    let wins = match final_score.winner() {
+        Team::Home => &HOME_WINS,
+        Team::Away => &AWAY_WINS,
+    };
+
+    wins.fetch_add(1, SeqCst);
+}
+
+/// Derives a per-game seed from the run's master seed so that parallel and
+/// `--disable-parallel` runs draw identical, reproducible random numbers for the same
+/// `--seed`, regardless of what order the games happen to finish in.
+fn game_seed(master_seed: u64, game_index: usize) -> u64 {
+    master_seed ^ game_index as u64
+}
+
 macro_rules! sim_games {
-    ($iter:expr, $opts:expr) => {{
+    ($iter:expr, $opts:expr, $master_seed:expr, $home_score_percent:expr, $away_score_percent:expr, $extra_innings_score_percent:expr) => {{
         let Options {
-            regular_score_percent,
-            extra_innings_score_percent,
-            skip_first_nine_innings,
+            regulation_innings,
+            skip_regulation_innings,
             ..
         } = *$opts;
+        let master_seed = $master_seed;
+        let home_score_percent = $home_score_percent;
+        let away_score_percent = $away_score_percent;
+        let extra_innings_score_percent = $extra_innings_score_percent;
 
         $iter
-            .filter_map(move |_| {
-                let inning = simulate_game(
-                    regular_score_percent,
+            .filter_map(move |game_index| {
+                let final_score = simulate_game(
+                    home_score_percent,
+                    away_score_percent,
                     extra_innings_score_percent,
-                    skip_first_nine_innings,
-                )
-                .inning;
+                    regulation_innings,
+                    skip_regulation_innings,
+                    game_seed(master_seed, game_index),
+                );
 
-                (inning > NUM_INNINGS_DEFAULT).then(|| inning)
+                update_win_count(&final_score);
+
+                (final_score.inning > regulation_innings).then(|| final_score.inning)
             })
-            .inspect(|num_innings| update_inning_count(*num_innings))
+            .inspect(|num_innings| update_inning_count(*num_innings, regulation_innings))
             .count()
     }};
 }
 
-fn simulate_inning_counts(opts: &Options) -> usize {
-    let iter = 0..opts.num_games;
+fn simulate_inning_counts(
+    opts: &Options,
+    num_games: usize,
+    home_score_percent: u8,
+    away_score_percent: u8,
+    extra_innings_score_percent: u8,
+) -> usize {
+    let iter = 0..num_games;
+    let master_seed = opts.seed.unwrap_or_else(|| thread_rng().gen());
 
     if opts.disable_parallel {
-        sim_games!(iter, opts)
+        sim_games!(
+            iter,
+            opts,
+            master_seed,
+            home_score_percent,
+            away_score_percent,
+            extra_innings_score_percent
+        )
     } else {
-        sim_games!(iter.into_par_iter(), opts)
+        sim_games!(
+            iter.into_par_iter(),
+            opts,
+            master_seed,
+            home_score_percent,
+            away_score_percent,
+            extra_innings_score_percent
+        )
     }
 }
 
-fn formatted_usize(number: usize) -> Buffer {
-    let mut buffer = Buffer::new();
-    buffer.write_formatted(&number, &Locale::en);
-    buffer
+/// Zeroes out [`INNING_COUNTS`] and the win-count atomics so the same process can simulate
+/// several independent batches of games, as [`fit::fit`] does for each candidate parameter pair.
+fn reset_counts() {
+    for count in &INNING_COUNTS {
+        count.store(0, SeqCst);
+    }
+
+    HOME_WINS.store(0, SeqCst);
+    AWAY_WINS.store(0, SeqCst);
 }
 
-fn print_inning_counts(total_games: usize, num_extra_innings_games: usize) {
-    let total_games_display = format!("{}", formatted_usize(total_games));
+/// Simulates `num_games` games under the given scoring rates and returns the resulting
+/// extra-inning-length distribution as fractions of `num_games`, indexed the same way as
+/// [`INNING_COUNTS`]. Used by [`fit::fit`] to score a candidate pair against a target histogram
+/// at a much smaller `num_games` than a full `--num-games` run, so a search iteration stays cheap.
+fn simulated_fractions(
+    opts: &Options,
+    num_games: usize,
+    home_score_percent: u8,
+    away_score_percent: u8,
+    extra_innings_score_percent: u8,
+) -> Vec<f64> {
+    reset_counts();
+    simulate_inning_counts(opts, num_games, home_score_percent, away_score_percent, extra_innings_score_percent);
 
-    println!("Total games played: {}", total_games_display);
-    println!(
-        "Number of extra inning games: {}",
-        formatted_usize(num_extra_innings_games)
-    );
-    println!("\nNumber of games with <n> innings:");
+    INNING_COUNTS
+        .iter()
+        .map(|count| count.load(SeqCst) as f64 / num_games as f64)
+        .collect()
+}
 
-    for (inning, count) in INNING_COUNTS
+fn simulated_histogram(total_games: usize, num_extra_innings_games: usize, regulation_innings: u8) -> InningHistogram {
+    let counts = INNING_COUNTS
         .iter()
         .map(|count| count.load(SeqCst))
         .enumerate()
         .filter(|(_, count)| *count > 0)
-    {
-        print!("  {} innings: ", inning + 10);
-        println!(
-            "{count:>width$}",
-            count = formatted_usize(count).as_str(),
-            width = total_games_display.chars().count(),
-        );
+        .map(|(offset, count)| InningCount {
+            innings: offset as u8 + regulation_innings + 1,
+            count: count as f64,
+        })
+        .collect();
+
+    InningHistogram {
+        total_games,
+        num_extra_innings_games: num_extra_innings_games as f64,
+        counts,
+        win_breakdown: Some(WinBreakdown {
+            home_wins: HOME_WINS.load(SeqCst),
+            away_wins: AWAY_WINS.load(SeqCst),
+        }),
+    }
+}
+
+fn exact_histogram(
+    total_games: usize,
+    tied_after_regulation_probability: f64,
+    expected_counts: &[f64],
+    regulation_innings: u8,
+) -> InningHistogram {
+    let counts = expected_counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count >= 0.005)
+        .map(|(offset, &count)| InningCount {
+            innings: offset as u8 + regulation_innings + 1,
+            count,
+        })
+        .collect();
+
+    InningHistogram {
+        total_games,
+        num_extra_innings_games: total_games as f64 * tied_after_regulation_probability,
+        counts,
+        win_breakdown: None,
+    }
+}
+
+fn open_output(output_file: &Option<PathBuf>) -> io::Result<Box<dyn Write>> {
+    match output_file {
+        Some(path) => Ok(Box::new(File::create(path)?)),
+        None => Ok(Box::new(io::stdout())),
     }
 }
 
@@ -262,8 +484,84 @@ fn main() -> Result<(), u8> {
         return Err(2);
     }
 
-    let inning_counts = simulate_inning_counts(&args);
-    print_inning_counts(args.num_games, inning_counts);
+    let home_score_percent = args.home_score_percent.unwrap_or(args.regular_score_percent);
+    let away_score_percent = args.away_score_percent.unwrap_or(args.regular_score_percent);
+
+    if !(1..=99).contains(&home_score_percent) {
+        eprintln!("--home-score-percent must be between 1 and 99 (inclusive)");
+        return Err(3);
+    }
+
+    if !(1..=99).contains(&away_score_percent) {
+        eprintln!("--away-score-percent must be between 1 and 99 (inclusive)");
+        return Err(4);
+    }
+
+    if args.regulation_innings < 1 {
+        eprintln!("--regulation-innings must be at least 1");
+        return Err(5);
+    }
+
+    if args.fit {
+        let target_histogram = args.target_histogram.as_ref().ok_or_else(|| {
+            eprintln!("--fit requires --target-histogram");
+            6
+        })?;
+
+        let target = fit::parse_target_histogram(target_histogram).map_err(|err| {
+            eprintln!("failed to read --target-histogram: {}", err);
+            7
+        })?;
+
+        let result = fit::fit(&args, &target, Duration::from_millis(args.time_limit_ms));
+
+        println!(
+            "regular_score_percent={} extra_innings_score_percent={} residual_error={:.6}",
+            result.candidate.regular_score_percent,
+            result.candidate.extra_innings_score_percent,
+            result.residual_error
+        );
+
+        return Ok(());
+    }
+
+    let histogram = if args.exact {
+        let (tied_after_regulation_probability, expected_counts) = exact::expected_inning_counts(
+            args.regular_score_percent,
+            args.extra_innings_score_percent,
+            args.regulation_innings,
+            args.exact_max_runs,
+            args.exact_precision,
+            args.num_games,
+            args.skip_regulation_innings,
+        );
+
+        exact_histogram(
+            args.num_games,
+            tied_after_regulation_probability,
+            &expected_counts,
+            args.regulation_innings,
+        )
+    } else {
+        let inning_counts = simulate_inning_counts(
+            &args,
+            args.num_games,
+            home_score_percent,
+            away_score_percent,
+            args.extra_innings_score_percent,
+        );
+        simulated_histogram(args.num_games, inning_counts, args.regulation_innings)
+    };
+
+    let mut out = open_output(&args.output_file).map_err(|err| {
+        eprintln!("failed to open --output-file: {}", err);
+        8
+    })?;
+
+    args.output.write(&histogram, out.as_mut()).map_err(|err| {
+        eprintln!("failed to write output: {}", err);
+        9
+    })?;
 
     Ok(())
 }
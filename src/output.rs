@@ -0,0 +1,190 @@
+//! Pluggable writers for the inning-length histogram, selected by `--output`.
+//!
+//! Besides the default human-readable table, the `csv` and `json` writers let results be piped
+//! into plotting or downstream analysis instead of scraping the aligned text output.
+
+#[cfg(test)]
+mod test;
+
+use num_format::{Buffer, Locale};
+use std::io::{self, Write};
+
+/// One row of the inning-length histogram: `count` games finished with `innings` total
+/// innings, out of the enclosing [`InningHistogram`]'s `total_games`.
+///
+/// `count` is a plain `f64` so the same type can carry either exact sampled counts or the
+/// fractional expected counts produced by `--exact` mode.
+pub struct InningCount {
+    pub innings: u8,
+    pub count: f64,
+}
+
+/// How many games each team won, out of the enclosing [`InningHistogram`]'s `total_games`.
+/// `None` in `--exact` mode, which computes the inning-length distribution without modeling
+/// which team wins.
+pub struct WinBreakdown {
+    pub home_wins: usize,
+    pub away_wins: usize,
+}
+
+/// A full inning-length histogram, ready to hand to an [`OutputFormat`] writer.
+pub struct InningHistogram {
+    pub total_games: usize,
+    pub num_extra_innings_games: f64,
+    pub counts: Vec<InningCount>,
+    pub win_breakdown: Option<WinBreakdown>,
+}
+
+/// Output format for the inning-length histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The original human-readable, comma-grouped table.
+    Table,
+    /// `innings,count,fraction` rows.
+    Csv,
+    /// `{ total_games, num_extra_innings_games, win_breakdown, counts: [{ innings, count, fraction }] }`,
+    /// where `win_breakdown` is `{ home_wins, away_wins }` or `null`.
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown output format `{}` (expected `table`, `csv`, or `json`)",
+                other
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn write(self, histogram: &InningHistogram, out: &mut dyn Write) -> io::Result<()> {
+        match self {
+            Self::Table => write_table(histogram, out),
+            Self::Csv => write_csv(histogram, out),
+            Self::Json => write_json(histogram, out),
+        }
+    }
+}
+
+fn formatted_usize(number: usize) -> Buffer {
+    let mut buffer = Buffer::new();
+    buffer.write_formatted(&number, &Locale::en);
+    buffer
+}
+
+/// Renders `count` the way the table writer always has: as a comma-grouped integer when it's a
+/// whole number (the common sampled-count case), or to two decimal places otherwise (the
+/// fractional expected-count case from `--exact` mode).
+fn format_count(count: f64) -> String {
+    if count.is_finite() && count.fract() == 0.0 {
+        formatted_usize(count as usize).to_string()
+    } else {
+        format!("{:.2}", count)
+    }
+}
+
+fn write_table(histogram: &InningHistogram, out: &mut dyn Write) -> io::Result<()> {
+    let total_games_display = formatted_usize(histogram.total_games).to_string();
+
+    writeln!(out, "Total games played: {}", total_games_display)?;
+    writeln!(
+        out,
+        "Number of extra inning games: {}",
+        format_count(histogram.num_extra_innings_games)
+    )?;
+    if let Some(win_breakdown) = &histogram.win_breakdown {
+        writeln!(out, "Home team wins: {}", formatted_usize(win_breakdown.home_wins))?;
+        writeln!(out, "Away team wins: {}", formatted_usize(win_breakdown.away_wins))?;
+    }
+
+    writeln!(out, "\nNumber of games with <n> innings:")?;
+
+    for entry in &histogram.counts {
+        writeln!(
+            out,
+            "  {} innings: {:>width$}",
+            entry.innings,
+            format_count(entry.count),
+            width = total_games_display.chars().count(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Renders `count` as a plain (no thousands separators) number: as an integer when it's a
+/// whole number, or to six decimal places otherwise. Used for the CSV and JSON writers, where
+/// the full `f64` precision of an `--exact` expected count would be noise.
+fn numeric_string(count: f64) -> String {
+    if count.is_finite() && count.fract() == 0.0 {
+        (count as i64).to_string()
+    } else {
+        format!("{:.6}", count)
+    }
+}
+
+// The win breakdown isn't part of the `innings,count,fraction` contract and has no column of
+// its own to live in, so unlike the table and JSON writers, CSV output omits it; a `#`-prefixed
+// comment row would break naive consumers (e.g. `pandas.read_csv` without `comment='#'`) on the
+// column-count mismatch with the header it precedes.
+fn write_csv(histogram: &InningHistogram, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "innings,count,fraction")?;
+
+    for entry in &histogram.counts {
+        let fraction = entry.count / histogram.total_games as f64;
+        writeln!(
+            out,
+            "{},{},{:.6}",
+            entry.innings,
+            numeric_string(entry.count),
+            fraction
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_json(histogram: &InningHistogram, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"total_games\": {},", histogram.total_games)?;
+    writeln!(
+        out,
+        "  \"num_extra_innings_games\": {},",
+        numeric_string(histogram.num_extra_innings_games)
+    )?;
+    match &histogram.win_breakdown {
+        Some(win_breakdown) => writeln!(
+            out,
+            "  \"win_breakdown\": {{ \"home_wins\": {}, \"away_wins\": {} }},",
+            win_breakdown.home_wins, win_breakdown.away_wins
+        )?,
+        None => writeln!(out, "  \"win_breakdown\": null,")?,
+    };
+    writeln!(out, "  \"counts\": [")?;
+
+    for (index, entry) in histogram.counts.iter().enumerate() {
+        let fraction = entry.count / histogram.total_games as f64;
+        let separator = if index + 1 < histogram.counts.len() { "," } else { "" };
+
+        writeln!(
+            out,
+            "    {{ \"innings\": {}, \"count\": {}, \"fraction\": {:.6} }}{}",
+            entry.innings,
+            numeric_string(entry.count),
+            fraction,
+            separator
+        )?;
+    }
+
+    writeln!(out, "  ]")?;
+    write!(out, "}}")?;
+
+    Ok(())
+}
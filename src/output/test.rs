@@ -0,0 +1,76 @@
+use super::*;
+
+fn sample_histogram() -> InningHistogram {
+    InningHistogram {
+        total_games: 1000,
+        num_extra_innings_games: 100.0,
+        counts: vec![
+            InningCount { innings: 10, count: 60.0 },
+            InningCount { innings: 11, count: 25.5 },
+        ],
+        win_breakdown: Some(WinBreakdown {
+            home_wins: 470,
+            away_wins: 430,
+        }),
+    }
+}
+
+#[test]
+fn format_count_renders_whole_numbers_without_decimals() {
+    assert_eq!(format_count(1_000_000.0), "1,000,000");
+}
+
+#[test]
+fn format_count_renders_fractions_with_two_decimals() {
+    assert_eq!(format_count(25.5), "25.50");
+}
+
+#[test]
+fn write_csv_emits_header_and_fraction_column() {
+    let mut out = Vec::new();
+    write_csv(&sample_histogram(), &mut out).unwrap();
+
+    let csv = String::from_utf8(out).unwrap();
+    assert_eq!(csv, "innings,count,fraction\n10,60,0.060000\n11,25.500000,0.025500\n");
+}
+
+#[test]
+fn write_csv_omits_win_breakdown() {
+    let mut out = Vec::new();
+    write_csv(&sample_histogram(), &mut out).unwrap();
+
+    let csv = String::from_utf8(out).unwrap();
+    assert!(!csv.contains("home_wins"));
+}
+
+#[test]
+fn write_json_emits_counts_array() {
+    let mut out = Vec::new();
+    write_json(&sample_histogram(), &mut out).unwrap();
+
+    let json = String::from_utf8(out).unwrap();
+    assert!(json.contains("\"total_games\": 1000,"));
+    assert!(json.contains("\"win_breakdown\": { \"home_wins\": 470, \"away_wins\": 430 },"));
+    assert!(json.contains("\"innings\": 10, \"count\": 60, \"fraction\": 0.060000"));
+    assert!(json.ends_with('}'));
+}
+
+#[test]
+fn write_json_emits_null_win_breakdown_when_absent() {
+    let mut histogram = sample_histogram();
+    histogram.win_breakdown = None;
+
+    let mut out = Vec::new();
+    write_json(&histogram, &mut out).unwrap();
+
+    let json = String::from_utf8(out).unwrap();
+    assert!(json.contains("\"win_breakdown\": null,"));
+}
+
+#[test]
+fn output_format_from_str() {
+    assert_eq!("table".parse(), Ok(OutputFormat::Table));
+    assert_eq!("csv".parse(), Ok(OutputFormat::Csv));
+    assert_eq!("json".parse(), Ok(OutputFormat::Json));
+    assert!("bogus".parse::<OutputFormat>().is_err());
+}
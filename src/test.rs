@@ -7,6 +7,7 @@ fn game_over_top_nine_away_winning() {
         inning: 9,
         home_team_runs: 0,
         away_team_runs: 1,
+        ..Default::default()
     };
 
     assert!(!state.is_over());
@@ -19,6 +20,7 @@ fn game_over_top_nine_home_winning() {
         inning: 9,
         home_team_runs: 1,
         away_team_runs: 0,
+        ..Default::default()
     };
 
     assert!(state.is_over());
@@ -31,6 +33,7 @@ fn game_over_top_nine_tied() {
         inning: 9,
         home_team_runs: 0,
         away_team_runs: 0,
+        ..Default::default()
     };
 
     assert!(!state.is_over());
@@ -43,6 +46,7 @@ fn game_over_bottom_nine_away_winning() {
         inning: 9,
         home_team_runs: 0,
         away_team_runs: 1,
+        ..Default::default()
     };
 
     assert!(state.is_over());
@@ -55,6 +59,7 @@ fn game_over_bottom_nine_home_winning() {
         inning: 9,
         home_team_runs: 1,
         away_team_runs: 0,
+        ..Default::default()
     };
 
     assert!(state.is_over());
@@ -67,6 +72,7 @@ fn game_over_bottom_nine_tied() {
         inning: 9,
         home_team_runs: 0,
         away_team_runs: 0,
+        ..Default::default()
     };
 
     assert!(!state.is_over());
@@ -79,6 +85,7 @@ fn game_over_top_away_winning() {
         inning: 10,
         home_team_runs: 0,
         away_team_runs: 1,
+        ..Default::default()
     };
 
     assert!(!state.is_over());